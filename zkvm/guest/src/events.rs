@@ -0,0 +1,51 @@
+//! Sol event signatures this guest knows how to attest to. `main` selects among these at
+//! runtime by matching the caller-supplied topic0 against each event's `SIGNATURE_HASH`.
+//! Also holds the `INttManagerPeers` view functions that `main` calls on the emitter.
+
+use alloy_sol_types::sol;
+
+sol! {
+    interface INttManager {
+        /// @notice Emitted when a message is sent from the nttManager.
+        /// @dev Topic0
+        ///      0x3e6ae56314c6da8b461d872f41c6d0bb69317b9d0232805aaccfa45df1a16fa0.
+        /// @param digest The digest of the message.
+        event TransferSent(bytes32 indexed digest);
+
+        /// @notice Emitted when an inbound message is held back by the rate limiter.
+        /// @param digest The digest of the queued message.
+        event InboundTransferQueued(bytes32 digest);
+    }
+}
+
+sol! {
+    interface INttManagerPeers {
+        /// @notice The registered sibling manager for a given Wormhole chain id.
+        struct NttManagerPeer {
+            bytes32 peerAddress;
+            uint8 tokenDecimals;
+        }
+
+        /// @notice An inbound rate limit, trimmed to the token's on-chain decimals.
+        struct TrimmedAmount {
+            uint64 amount;
+            uint8 decimals;
+        }
+
+        function getPeer(uint16 chainId_) external view returns (NttManagerPeer memory);
+        function getInboundLimitParams(uint16 chainId_) external view returns (TrimmedAmount memory);
+    }
+}
+
+sol! {
+    interface IWormhole {
+        /// @notice Emitted by the core bridge whenever a cross-chain message is published.
+        event LogMessagePublished(
+            address indexed sender,
+            uint64 sequence,
+            uint32 nonce,
+            bytes payload,
+            uint8 consistencyLevel
+        );
+    }
+}
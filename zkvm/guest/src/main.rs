@@ -1,24 +1,23 @@
 #![no_main]
 
-use alloy_primitives::{Address, FixedBytes};
-use alloy_sol_types::{sol, SolValue};
+use alloy_primitives::{keccak256, Address, FixedBytes};
+use alloy_sol_types::{sol, SolEvent, SolValue};
 use risc0_steel::{
-    ethereum::{EthEvmInput, ETH_MAINNET_CHAIN_SPEC},
-    Commitment, Event,
+    ethereum::{
+        EthChainSpec, EthEvmInput, ARB_MAINNET_CHAIN_SPEC, BASE_MAINNET_CHAIN_SPEC,
+        ETH_MAINNET_CHAIN_SPEC, ETH_SEPOLIA_CHAIN_SPEC, OP_MAINNET_CHAIN_SPEC,
+    },
+    Account, Commitment, Contract, Event,
 };
 use risc0_zkvm::guest::env;
+use std::collections::HashMap;
 
-risc0_zkvm::guest::entry!(main);
+mod events;
+mod ntt;
 
-sol! {
-    interface INttManager {
-        /// @notice Emitted when a message is sent from the nttManager.
-        /// @dev Topic0
-        ///      0x3e6ae56314c6da8b461d872f41c6d0bb69317b9d0232805aaccfa45df1a16fa0.
-        /// @param digest The digest of the message.
-        event TransferSent(bytes32 indexed digest);
-    }
-}
+use events::{INttManager, INttManagerPeers, IWormhole};
+
+risc0_zkvm::guest::entry!(main);
 
 sol! {
     /// @notice Journal that is committed to by the guest.
@@ -27,10 +26,34 @@ sol! {
         // which can be verified against the BoundlessReceiver contract
         Commitment commitment;
 
-        // Commits to the ntt manager message that was sent
-        bytes32 nttManagerMessageDigest;
+        // Chain id the event was queried against
+        uint64 chainId;
+        // topic0 of the event schema the digests below were queried against
+        bytes32 topic0;
+        // Domain-separated Merkle root over the per-log digest of every proven log
+        // (last leaf duplicated on odd counts)
+        bytes32 nttManagerMessagesRoot;
+        // Number of leaves committed to by `nttManagerMessagesRoot`
+        uint32 messageCount;
         // Commits to the NTT manager that emitted the message (wormhole encoded address)
         bytes32 emitterNttManager;
+        // extcodehash of the emitter at the committed block
+        bytes32 emitterCodeHash;
+
+        // Decoded `NativeTokenTransfer` fields per message, in the same order as the leaves
+        // of `nttManagerMessagesRoot`. Empty unless topic0 selects a schema with a decodable
+        // message.
+        bytes32[] recipients;
+        uint16[] destinationChains;
+        bytes32[] sourceTokens;
+        uint8[] decimals;
+        uint64[] trimmedAmounts;
+
+        // Registered peer manager and inbound rate limit per message's destination chain,
+        // read from the emitter via `getPeer`/`getInboundLimitParams` at the committed block
+        bytes32[] peerAddresses;
+        uint64[] inboundLimitAmounts;
+        uint8[] inboundLimitDecimals;
     }
 }
 
@@ -38,27 +61,378 @@ fn main() {
     // Read the input from the guest environment.
     let input: EthEvmInput = env::read();
     let contract_addr: Address = env::read();
-    let log_index: u32 = env::read();
+    let log_indices: Vec<u32> = env::read();
+    let chain_id: u64 = env::read();
+    // Selects which registered event schema to query the logs against.
+    let topic0: FixedBytes<32> = env::read();
+    // Raw `NttManagerMessage` bytes and source Wormhole chain id per proven log, in the same
+    // order as `digests`. Must be empty unless `topic0` selects a schema with a decodable
+    // message (see `decodes_ntt_message`).
+    let raw_messages: Vec<Vec<u8>> = env::read();
+    let source_chain_ids: Vec<u16> = env::read();
+    // Optional pinned code hash for the emitter; if set, the guest refuses to prove against a
+    // contract whose deployed bytecode doesn't match what the caller expects.
+    let expected_emitter_code_hash: Option<FixedBytes<32>> = env::read();
 
-    // Converts the input into a `EvmEnv` for execution.
-    let env = input.into_env(&ETH_MAINNET_CHAIN_SPEC);
+    // Converts the input into a `EvmEnv` for execution, pinned to the chain
+    // the caller asked us to prove against.
+    let chain_spec = chain_spec_for_id(chain_id);
+    let env = input.into_env(chain_spec);
 
-    // Query the `TransferSent` events of the contract and pick out the requested log index
-    let event = Event::new::<INttManager::TransferSent>(&env);
-    let log = &event.address(contract_addr).query()[log_index as usize];
+    // Dispatch on topic0 to the registered event schema, and pick out the requested log indices
+    // (or every log in the block if none were specified).
+    let digests: Vec<FixedBytes<32>> = if topic0 == INttManager::TransferSent::SIGNATURE_HASH {
+        let logs = Event::new::<INttManager::TransferSent>(&env)
+            .address(contract_addr)
+            .query();
+        select_digests(&logs, &log_indices, |log| log.digest)
+    } else if topic0 == INttManager::InboundTransferQueued::SIGNATURE_HASH {
+        let logs = Event::new::<INttManager::InboundTransferQueued>(&env)
+            .address(contract_addr)
+            .query();
+        select_digests(&logs, &log_indices, |log| log.digest)
+    } else if topic0 == IWormhole::LogMessagePublished::SIGNATURE_HASH {
+        let logs = Event::new::<IWormhole::LogMessagePublished>(&env)
+            .address(contract_addr)
+            .query();
+        select_digests(&logs, &log_indices, |log| {
+            keccak256(
+                (
+                    log.sender,
+                    log.sequence,
+                    log.nonce,
+                    log.payload.clone(),
+                    log.consistencyLevel,
+                )
+                    .abi_encode(),
+            )
+        })
+    } else {
+        panic!("unsupported event topic0: {topic0}");
+    };
+    let message_count = digests.len() as u32;
+
+    let messages = decode_messages(topic0, &digests, &raw_messages, &source_chain_ids);
+
+    let mut recipients = Vec::with_capacity(messages.len());
+    let mut destination_chains = Vec::with_capacity(messages.len());
+    let mut source_tokens = Vec::with_capacity(messages.len());
+    let mut decimals = Vec::with_capacity(messages.len());
+    let mut trimmed_amounts = Vec::with_capacity(messages.len());
+    for message in &messages {
+        recipients.push(message.transfer.to);
+        destination_chains.push(message.transfer.to_chain);
+        source_tokens.push(message.transfer.source_token);
+        decimals.push(message.transfer.decimals);
+        trimmed_amounts.push(message.transfer.trimmed_amount);
+    }
+
+    // Read the peer manager and inbound limit per destination chain, caching by chain id since
+    // a batch commonly repeats destinations.
+    let contract = Contract::new(contract_addr, &env);
+    let mut peer_cache: HashMap<u16, (FixedBytes<32>, u64, u8)> = HashMap::new();
+    let mut peer_addresses = Vec::with_capacity(messages.len());
+    let mut inbound_limit_amounts = Vec::with_capacity(messages.len());
+    let mut inbound_limit_decimals = Vec::with_capacity(messages.len());
+    for &to_chain in &destination_chains {
+        let &(peer_address, limit_amount, limit_decimals) =
+            peer_cache.entry(to_chain).or_insert_with(|| {
+                let peer = contract
+                    .call_builder(&INttManagerPeers::getPeerCall { chainId_: to_chain })
+                    .call();
+                let limit = contract
+                    .call_builder(&INttManagerPeers::getInboundLimitParamsCall { chainId_: to_chain })
+                    .call();
+                (peer.peerAddress, limit.amount, limit.decimals)
+            });
+        peer_addresses.push(peer_address);
+        inbound_limit_amounts.push(limit_amount);
+        inbound_limit_decimals.push(limit_decimals);
+    }
+
+    let root = merkle_root(digests);
+
+    // Read the emitter's extcodehash at the committed block to pin the event to the deployed NTT
+    // manager bytecode, the same guarantee an `eth_getCode` check would give off-chain.
+    let emitter_code_hash = Account::new(contract_addr, &env).query().code_hash;
+    if let Some(expected) = expected_emitter_code_hash {
+        assert_eq!(
+            emitter_code_hash, expected,
+            "emitter code hash does not match expected hash"
+        );
+    }
 
-    // Commit to this message as being from the NTT manager contract in the block committed to by the env commitment
+    // Commit the batch root as being from the NTT manager contract in the block committed to by
+    // the env commitment. One Steel proof now covers every transfer in `digests`; the receiver
+    // accepts an individual message by supplying its Merkle inclusion path against this root.
     let journal = Journal {
         commitment: env.into_commitment(),
-        nttManagerMessageDigest: log.digest,
+        chainId: chain_id,
+        topic0,
+        nttManagerMessagesRoot: root,
+        messageCount: message_count,
         emitterNttManager: to_universal_address(contract_addr),
+        emitterCodeHash: emitter_code_hash,
+        recipients,
+        destinationChains: destination_chains,
+        sourceTokens: source_tokens,
+        decimals,
+        trimmedAmounts: trimmed_amounts,
+        peerAddresses: peer_addresses,
+        inboundLimitAmounts: inbound_limit_amounts,
+        inboundLimitDecimals: inbound_limit_decimals,
     };
     env::commit_slice(&journal.abi_encode());
 }
 
+/// Whether `topic0` selects an event schema whose digest commits to a decodable
+/// `NttManagerMessage` (`TransferSent` and `InboundTransferQueued` both use the same
+/// `nttManagerMessageDigest` commitment; `LogMessagePublished` does not).
+fn decodes_ntt_message(topic0: FixedBytes<32>) -> bool {
+    topic0 == INttManager::TransferSent::SIGNATURE_HASH
+        || topic0 == INttManager::InboundTransferQueued::SIGNATURE_HASH
+}
+
+/// Verifies and decodes the `NttManagerMessage` behind each of `digests`, in order.
+///
+/// For schemas that don't carry a decodable message, asserts `raw_messages`/`source_chain_ids`
+/// were left empty and returns no messages, keeping every downstream per-message array aligned
+/// with `digests` (empty when `digests` has no decodable messages, one entry per digest otherwise).
+fn decode_messages(
+    topic0: FixedBytes<32>,
+    digests: &[FixedBytes<32>],
+    raw_messages: &[Vec<u8>],
+    source_chain_ids: &[u16],
+) -> Vec<ntt::NttManagerMessage> {
+    if !decodes_ntt_message(topic0) {
+        assert!(
+            raw_messages.is_empty() && source_chain_ids.is_empty(),
+            "raw_messages/source_chain_ids must be empty for a topic0 with no decodable NttManagerMessage"
+        );
+        return Vec::new();
+    }
+    assert_eq!(
+        raw_messages.len(),
+        digests.len(),
+        "raw_messages does not match the number of proven logs"
+    );
+    assert_eq!(
+        source_chain_ids.len(),
+        digests.len(),
+        "source_chain_ids does not match the number of proven logs"
+    );
+    digests
+        .iter()
+        .zip(source_chain_ids.iter())
+        .zip(raw_messages.iter())
+        .map(|((&digest, &source_chain_id), raw_message)| {
+            ntt::verify_and_decode(source_chain_id, raw_message, digest)
+        })
+        .collect()
+}
+
+/// Picks out `indices` from `logs` (or every log, if `indices` is empty), mapping each to its
+/// commitment digest via `digest_of`.
+fn select_digests<T>(
+    logs: &[T],
+    indices: &[u32],
+    digest_of: impl Fn(&T) -> FixedBytes<32>,
+) -> Vec<FixedBytes<32>> {
+    if indices.is_empty() {
+        logs.iter().map(digest_of).collect()
+    } else {
+        indices.iter().map(|&index| digest_of(&logs[index as usize])).collect()
+    }
+}
+
+/// Domain separation tags distinguishing leaf hashes from internal node hashes, so a leaf can
+/// never be mistaken for the root of some subtree (the classic second-preimage weakness in a
+/// naive `keccak256(a||b)`-at-every-level Merkle tree).
+const MERKLE_LEAF_PREFIX: u8 = 0x00;
+const MERKLE_NODE_PREFIX: u8 = 0x01;
+
+/// Builds a Merkle root over `leaves` using keccak256 pair-hashing, duplicating the last node
+/// when a level has an odd number of nodes.
+fn merkle_root(leaves: Vec<FixedBytes<32>>) -> FixedBytes<32> {
+    assert!(!leaves.is_empty(), "no logs to prove");
+    let mut level: Vec<FixedBytes<32>> = leaves
+        .into_iter()
+        .map(|leaf| keccak256([&[MERKLE_LEAF_PREFIX][..], leaf.as_slice()].concat()))
+        .collect();
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push(*level.last().unwrap());
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                keccak256([&[MERKLE_NODE_PREFIX][..], pair[0].as_slice(), pair[1].as_slice()].concat())
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Resolves the `risc0_steel` chain spec matching an EIP-155 chain id.
+///
+/// NTT managers are deployed across many chains, so the guest has to be told
+/// which network's event it is attesting to rather than hardcoding mainnet.
+fn chain_spec_for_id(chain_id: u64) -> &'static EthChainSpec {
+    match chain_id {
+        1 => &ETH_MAINNET_CHAIN_SPEC,
+        11155111 => &ETH_SEPOLIA_CHAIN_SPEC,
+        10 => &OP_MAINNET_CHAIN_SPEC,
+        42161 => &ARB_MAINNET_CHAIN_SPEC,
+        8453 => &BASE_MAINNET_CHAIN_SPEC,
+        _ => panic!("unsupported chain id: {chain_id}"),
+    }
+}
+
 fn to_universal_address(addr: Address) -> FixedBytes<32> {
     let addr_bytes = addr.as_slice();
     let mut padded = [0u8; 32];
     padded[12..].copy_from_slice(addr_bytes);
     FixedBytes::from(padded)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(byte: u8) -> FixedBytes<32> {
+        FixedBytes::repeat_byte(byte)
+    }
+
+    fn leaf_hash(x: FixedBytes<32>) -> FixedBytes<32> {
+        keccak256([&[MERKLE_LEAF_PREFIX][..], x.as_slice()].concat())
+    }
+
+    fn node_hash(a: FixedBytes<32>, b: FixedBytes<32>) -> FixedBytes<32> {
+        keccak256([&[MERKLE_NODE_PREFIX][..], a.as_slice(), b.as_slice()].concat())
+    }
+
+    #[test]
+    fn merkle_root_two_leaves() {
+        let (a, b) = (leaf(0xaa), leaf(0xbb));
+        let expected = node_hash(leaf_hash(a), leaf_hash(b));
+        assert_eq!(merkle_root(vec![a, b]), expected);
+    }
+
+    #[test]
+    fn merkle_root_three_leaves_duplicates_last() {
+        let (a, b, c) = (leaf(0xaa), leaf(0xbb), leaf(0xcc));
+        let left = node_hash(leaf_hash(a), leaf_hash(b));
+        let right = node_hash(leaf_hash(c), leaf_hash(c));
+        let expected = node_hash(left, right);
+        assert_eq!(merkle_root(vec![a, b, c]), expected);
+    }
+
+    #[test]
+    fn select_digests_returns_all_when_indices_empty() {
+        let logs = vec![leaf(1), leaf(2), leaf(3)];
+        let digests = select_digests(&logs, &[], |&x| x);
+        assert_eq!(digests, logs);
+    }
+
+    #[test]
+    fn select_digests_picks_requested_indices_in_order() {
+        let logs = vec![leaf(10), leaf(20), leaf(30)];
+        let digests = select_digests(&logs, &[2, 0], |&x| x);
+        assert_eq!(digests, vec![leaf(30), leaf(10)]);
+    }
+
+    #[test]
+    fn chain_spec_for_id_resolves_known_chains() {
+        for chain_id in [1, 11155111, 10, 42161, 8453] {
+            chain_spec_for_id(chain_id);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unsupported chain id")]
+    fn chain_spec_for_id_panics_on_unknown_chain() {
+        chain_spec_for_id(1337);
+    }
+
+    fn raw_ntt_message(source_chain_id: u16) -> (Vec<u8>, FixedBytes<32>) {
+        let mut payload = vec![0x99, 0x4E, 0x54, 0x54]; // NativeTokenTransfer prefix
+        payload.push(6); // decimals
+        payload.extend_from_slice(&1u64.to_be_bytes()); // trimmed amount
+        payload.extend_from_slice(leaf(0x33).as_slice()); // source token
+        payload.extend_from_slice(leaf(0x44).as_slice()); // to
+        payload.extend_from_slice(&2u16.to_be_bytes()); // to chain
+
+        let mut raw_message = leaf(0x11).as_slice().to_vec(); // id
+        raw_message.extend_from_slice(leaf(0x22).as_slice()); // sender
+        raw_message.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        raw_message.extend_from_slice(&payload);
+
+        let digest = keccak256([&source_chain_id.to_be_bytes()[..], &raw_message].concat());
+        (raw_message, digest)
+    }
+
+    #[test]
+    fn decodes_ntt_message_for_transfer_sent_and_inbound_transfer_queued() {
+        assert!(decodes_ntt_message(INttManager::TransferSent::SIGNATURE_HASH));
+        assert!(decodes_ntt_message(
+            INttManager::InboundTransferQueued::SIGNATURE_HASH
+        ));
+        assert!(!decodes_ntt_message(
+            IWormhole::LogMessagePublished::SIGNATURE_HASH
+        ));
+    }
+
+    #[test]
+    fn decode_messages_empty_for_log_message_published() {
+        let digests = vec![leaf(1), leaf(2), leaf(3)];
+        let messages = decode_messages(
+            IWormhole::LogMessagePublished::SIGNATURE_HASH,
+            &digests,
+            &[],
+            &[],
+        );
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "raw_messages/source_chain_ids must be empty")]
+    fn decode_messages_rejects_non_empty_inputs_for_non_decodable_topic() {
+        let digests = vec![leaf(1)];
+        decode_messages(
+            IWormhole::LogMessagePublished::SIGNATURE_HASH,
+            &digests,
+            &[vec![0u8]],
+            &[1],
+        );
+    }
+
+    #[test]
+    fn decode_messages_decodes_transfer_sent_and_inbound_transfer_queued() {
+        let source_chain_id = 10002u16;
+        let (raw_message, digest) = raw_ntt_message(source_chain_id);
+        let digests = vec![digest];
+        let raw_messages = vec![raw_message];
+        let source_chain_ids = vec![source_chain_id];
+
+        for topic0 in [
+            INttManager::TransferSent::SIGNATURE_HASH,
+            INttManager::InboundTransferQueued::SIGNATURE_HASH,
+        ] {
+            let messages = decode_messages(topic0, &digests, &raw_messages, &source_chain_ids);
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0].transfer.to_chain, 2);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "raw_messages does not match")]
+    fn decode_messages_rejects_length_mismatch_for_decodable_topic() {
+        let digests = vec![leaf(1), leaf(2)];
+        decode_messages(
+            INttManager::TransferSent::SIGNATURE_HASH,
+            &digests,
+            &[],
+            &[],
+        );
+    }
+}
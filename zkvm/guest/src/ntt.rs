@@ -0,0 +1,150 @@
+//! Decoding for the NTT manager wire format: the raw `NttManagerMessage` bytes whose digest a
+//! `TransferSent` event emits, and the `NativeTokenTransfer` payload carried inside it.
+
+use alloy_primitives::{keccak256, FixedBytes};
+
+/// `NativeTokenTransfer` payloads are tagged with this 4-byte prefix (0x99 followed by ASCII
+/// "NTT") so a manager can distinguish transfer payloads from other message kinds.
+const NATIVE_TOKEN_TRANSFER_PREFIX: [u8; 4] = [0x99, 0x4E, 0x54, 0x54];
+
+/// A decoded `NttManagerMessage`, with its payload parsed as a `NativeTokenTransfer`.
+pub struct NttManagerMessage {
+    pub id: FixedBytes<32>,
+    pub sender: FixedBytes<32>,
+    pub transfer: NativeTokenTransfer,
+}
+
+/// The transfer instructions carried in an `NttManagerMessage` payload.
+pub struct NativeTokenTransfer {
+    pub decimals: u8,
+    pub trimmed_amount: u64,
+    pub source_token: FixedBytes<32>,
+    pub to: FixedBytes<32>,
+    pub to_chain: u16,
+}
+
+/// Recomputes `keccak256(abi.encodePacked(sourceChainId, nttManagerMessage))`, asserts it matches
+/// the digest the contract emitted, and decodes `raw_message` into its structured fields.
+///
+/// Panics if the digest doesn't match or the message isn't a well-formed `NativeTokenTransfer`,
+/// since that means the caller handed the guest a message it didn't actually emit.
+pub fn verify_and_decode(
+    source_chain_id: u16,
+    raw_message: &[u8],
+    expected_digest: FixedBytes<32>,
+) -> NttManagerMessage {
+    let digest = keccak256([&source_chain_id.to_be_bytes()[..], raw_message].concat());
+    assert_eq!(digest, expected_digest, "ntt manager message digest mismatch");
+
+    // NttManagerMessage { bytes32 id; bytes32 sender; bytes payload }
+    let id = FixedBytes::from_slice(&raw_message[0..32]);
+    let sender = FixedBytes::from_slice(&raw_message[32..64]);
+    let payload_len = u16::from_be_bytes([raw_message[64], raw_message[65]]) as usize;
+    let payload = &raw_message[66..66 + payload_len];
+
+    NttManagerMessage {
+        id,
+        sender,
+        transfer: decode_native_token_transfer(payload),
+    }
+}
+
+fn decode_native_token_transfer(payload: &[u8]) -> NativeTokenTransfer {
+    assert_eq!(
+        payload[0..4],
+        NATIVE_TOKEN_TRANSFER_PREFIX,
+        "unexpected NativeTokenTransfer prefix"
+    );
+
+    NativeTokenTransfer {
+        decimals: payload[4],
+        trimmed_amount: u64::from_be_bytes(payload[5..13].try_into().unwrap()),
+        source_token: FixedBytes::from_slice(&payload[13..45]),
+        to: FixedBytes::from_slice(&payload[45..77]),
+        to_chain: u16::from_be_bytes([payload[77], payload[78]]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_raw_message(
+        id: FixedBytes<32>,
+        sender: FixedBytes<32>,
+        decimals: u8,
+        trimmed_amount: u64,
+        source_token: FixedBytes<32>,
+        to: FixedBytes<32>,
+        to_chain: u16,
+    ) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&NATIVE_TOKEN_TRANSFER_PREFIX);
+        payload.push(decimals);
+        payload.extend_from_slice(&trimmed_amount.to_be_bytes());
+        payload.extend_from_slice(source_token.as_slice());
+        payload.extend_from_slice(to.as_slice());
+        payload.extend_from_slice(&to_chain.to_be_bytes());
+
+        let mut raw_message = Vec::new();
+        raw_message.extend_from_slice(id.as_slice());
+        raw_message.extend_from_slice(sender.as_slice());
+        raw_message.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        raw_message.extend_from_slice(&payload);
+        raw_message
+    }
+
+    #[test]
+    fn verify_and_decode_known_vector() {
+        let id = FixedBytes::<32>::repeat_byte(0x11);
+        let sender = FixedBytes::<32>::repeat_byte(0x22);
+        let source_token = FixedBytes::<32>::repeat_byte(0x33);
+        let to = FixedBytes::<32>::repeat_byte(0x44);
+        let (decimals, trimmed_amount, to_chain) = (6u8, 123_456_789u64, 2u16);
+        let source_chain_id = 10002u16;
+
+        let raw_message = build_raw_message(
+            id,
+            sender,
+            decimals,
+            trimmed_amount,
+            source_token,
+            to,
+            to_chain,
+        );
+        let expected_digest = keccak256([&source_chain_id.to_be_bytes()[..], &raw_message].concat());
+
+        let message = verify_and_decode(source_chain_id, &raw_message, expected_digest);
+
+        assert_eq!(message.id, id);
+        assert_eq!(message.sender, sender);
+        assert_eq!(message.transfer.decimals, decimals);
+        assert_eq!(message.transfer.trimmed_amount, trimmed_amount);
+        assert_eq!(message.transfer.source_token, source_token);
+        assert_eq!(message.transfer.to, to);
+        assert_eq!(message.transfer.to_chain, to_chain);
+    }
+
+    #[test]
+    #[should_panic(expected = "ntt manager message digest mismatch")]
+    fn verify_and_decode_rejects_digest_mismatch() {
+        let raw_message = build_raw_message(
+            FixedBytes::<32>::repeat_byte(0x11),
+            FixedBytes::<32>::repeat_byte(0x22),
+            6,
+            1,
+            FixedBytes::<32>::repeat_byte(0x33),
+            FixedBytes::<32>::repeat_byte(0x44),
+            2,
+        );
+        verify_and_decode(1, &raw_message, FixedBytes::<32>::repeat_byte(0xff));
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected NativeTokenTransfer prefix")]
+    fn decode_native_token_transfer_rejects_wrong_prefix() {
+        let mut payload = vec![0u8; 79];
+        payload[0..4].copy_from_slice(&[0, 0, 0, 0]);
+        decode_native_token_transfer(&payload);
+    }
+}